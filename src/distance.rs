@@ -1,75 +1,207 @@
 // Logic to determine if a sparse grid should become a DenseGrid
 
 use crate::grid::CellCoordinate;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::Debug;
 
 ///
-/// Determines if given a iteratator of CellCoordinate if we should be sparse or not.
+/// Incrementally tracks grid occupancy (via `on_insert`/`on_remove`) and decides whether the
+/// grid should be in its sparse representation. Driven by hooks rather than a fresh
+/// `coord_iter` scan so a long run of inserts/removes doesn't cost O(n) per call.
 pub trait ConvertHeuristic: Debug {
-    fn convert_to_sparse<'a>(&'a self, it: Box<dyn Iterator<Item = CellCoordinate> + 'a>) -> bool;
+    /// Called after `coord` is inserted into the grid.
+    fn on_insert(&mut self, coord: CellCoordinate);
+    /// Called after `coord` is removed from the grid.
+    fn on_remove(&mut self, coord: CellCoordinate);
+    /// Whether the grid should currently be sparse, given everything observed so far.
+    fn should_convert_to_sparse(&mut self) -> bool;
+    /// Discards whatever incremental state was tracked and rebuilds it from a fresh scan of
+    /// every occupied coordinate. For bulk structural edits (row/column insert/delete) where
+    /// every shifted coordinate changes at once, this is cheaper and clearer than replaying an
+    /// `on_remove`/`on_insert` pair per shifted cell.
+    fn resync<'a>(&mut self, it: Box<dyn Iterator<Item = CellCoordinate> + 'a>);
 }
 
 ///
-/// Heuristic that looks at the max gap between rows or columns.
+/// Heuristic that looks at the max gap between occupied rows or columns. Uses hysteresis: once
+/// sparse, it takes dropping below `low_watermark` (not just `high_watermark`) to go back to
+/// dense, so a grid hovering near the boundary doesn't thrash and re-copy all its cells on
+/// alternating operations.
 #[derive(Debug)]
 pub struct MaxGapConvertHeuristic {
-    max_gap_threshold: usize,
+    high_watermark: usize,
+    low_watermark: usize,
+    currently_sparse: bool,
+    occupied: HashSet<CellCoordinate>,
+    row_refs: HashMap<usize, usize>,
+    col_refs: HashMap<usize, usize>,
+    row_gaps: GapMeasurement,
+    col_gaps: GapMeasurement,
 }
 
 impl MaxGapConvertHeuristic {
-    pub fn new(max_gap_threshold: usize) -> Self {
-        Self { max_gap_threshold }
+    /// `high_watermark`: max-gap threshold at or above which the grid converts to sparse.
+    /// `low_watermark`: once sparse, the max gap must drop below this (lower) threshold before
+    /// converting back to dense.
+    pub fn new(high_watermark: usize, low_watermark: usize) -> Self {
+        assert!(
+            low_watermark <= high_watermark,
+            "low_watermark must not exceed high_watermark"
+        );
+        Self {
+            high_watermark,
+            low_watermark,
+            currently_sparse: false,
+            occupied: HashSet::new(),
+            row_refs: HashMap::new(),
+            col_refs: HashMap::new(),
+            row_gaps: GapMeasurement::default(),
+            col_gaps: GapMeasurement::default(),
+        }
+    }
+}
+
+// increment `key`'s ref count, returning the count after the bump.
+fn bump_ref(refs: &mut HashMap<usize, usize>, key: usize) -> usize {
+    let count = refs.entry(key).or_insert(0);
+    *count += 1;
+    *count
+}
+
+// decrement `key`'s ref count, removing the entry once it hits zero. Returns the count after
+// the drop (0 if the key is now gone).
+fn drop_ref(refs: &mut HashMap<usize, usize>, key: usize) -> usize {
+    match refs.get_mut(&key) {
+        Some(count) if *count > 1 => {
+            *count -= 1;
+            *count
+        }
+        Some(_) => {
+            refs.remove(&key);
+            0
+        }
+        None => 0,
     }
 }
 
 impl ConvertHeuristic for MaxGapConvertHeuristic {
-    fn convert_to_sparse<'a>(&'a self, it: Box<dyn Iterator<Item = CellCoordinate> + 'a>) -> bool {
-        let mut row_gap_measurment = GapMeasurement::default();
-        let mut col_gap_measurment = GapMeasurement::default();
-        for coord in it {
-            row_gap_measurment.add(coord.row);
-            col_gap_measurment.add(coord.col);
+    fn on_insert(&mut self, coord: CellCoordinate) {
+        if !self.occupied.insert(coord) {
+            // already tracked at this exact coordinate; row/col occupancy is unchanged.
+            return;
+        }
+        if bump_ref(&mut self.row_refs, coord.row) == 1 {
+            self.row_gaps.add(coord.row);
+        }
+        if bump_ref(&mut self.col_refs, coord.col) == 1 {
+            self.col_gaps.add(coord.col);
+        }
+    }
+
+    fn on_remove(&mut self, coord: CellCoordinate) {
+        if !self.occupied.remove(&coord) {
+            return;
+        }
+        if drop_ref(&mut self.row_refs, coord.row) == 0 {
+            self.row_gaps.remove(coord.row);
+        }
+        if drop_ref(&mut self.col_refs, coord.col) == 0 {
+            self.col_gaps.remove(coord.col);
         }
+    }
+
+    fn should_convert_to_sparse(&mut self) -> bool {
+        let max_gap = self.row_gaps.max_gap().max(self.col_gaps.max_gap());
+        let threshold = if self.currently_sparse {
+            self.low_watermark
+        } else {
+            self.high_watermark
+        };
+        self.currently_sparse = max_gap >= threshold;
+        self.currently_sparse
+    }
 
-        return row_gap_measurment.max_gap >= self.max_gap_threshold
-            || col_gap_measurment.max_gap >= self.max_gap_threshold;
+    fn resync<'a>(&mut self, it: Box<dyn Iterator<Item = CellCoordinate> + 'a>) {
+        self.occupied.clear();
+        self.row_refs.clear();
+        self.col_refs.clear();
+        self.row_gaps = GapMeasurement::default();
+        self.col_gaps = GapMeasurement::default();
+        for coord in it {
+            self.on_insert(coord);
+        }
     }
 }
 
 ///
-/// Measures the gap between a sequence of numbers
+/// Measures the gap between a sequence of numbers, maintaining a sorted set of occupied
+/// positions (a `BTreeSet` so predecessor/successor lookups and insert/remove are all O(log n),
+/// not the O(n) shift a `Vec::insert`/`Vec::remove` would cost) and a multiset of the gaps
+/// between adjacent positions so the max gap is always available without rescanning.
 #[derive(Default, Debug)]
 struct GapMeasurement {
-    gaps: Vec<usize>,
-    max_gap: usize,
+    positions: BTreeSet<usize>,
+    // gap length -> how many adjacent pairs currently have that gap
+    gap_counts: BTreeMap<usize, usize>,
 }
+
 impl GapMeasurement {
-    // add a `value` in this case a coordinate and update the max gap seen
-    fn add(&mut self, value: usize) {
-        // check_pos is the position that the idx is, we need to check the gap on either side of it.
-        let check_pos = match self.gaps.binary_search(&value) {
-            Ok(_) => {
-                //already filled, keep moving
-                return;
+    fn max_gap(&self) -> usize {
+        self.gap_counts.keys().next_back().copied().unwrap_or(0)
+    }
+
+    fn bump_gap(&mut self, gap: usize) {
+        *self.gap_counts.entry(gap).or_insert(0) += 1;
+    }
+
+    fn drop_gap(&mut self, gap: usize) {
+        if let Some(count) = self.gap_counts.get_mut(&gap) {
+            if *count > 1 {
+                *count -= 1;
+            } else {
+                self.gap_counts.remove(&gap);
             }
-            Err(pos) => {
-                self.gaps.insert(pos, value);
-                pos
+        }
+    }
+
+    // add a `value` (e.g. a coordinate) and update the gap multiset.
+    fn add(&mut self, value: usize) {
+        if !self.positions.insert(value) {
+            return; // already present, keep moving
+        }
+
+        let left = self.positions.range(..value).next_back().copied();
+        let right = self.positions.range(value + 1..).next().copied();
+        match (left, right) {
+            (Some(left), Some(right)) => {
+                self.drop_gap(right - left);
+                self.bump_gap(value - left);
+                self.bump_gap(right - value);
             }
-        };
+            (Some(left), None) => self.bump_gap(value - left),
+            (None, Some(right)) => self.bump_gap(right - value),
+            (None, None) => {}
+        }
+    }
 
-        if check_pos > 0 {
-            //check the gap on the lower side
-            let lower_neighbor = self.gaps[check_pos - 1];
-            let gap = value - lower_neighbor;
-            self.max_gap = self.max_gap.max(gap);
+    // remove a previously-added `value`, merging its two neighboring gaps (if any) back into
+    // one rather than rescanning the whole sequence.
+    fn remove(&mut self, value: usize) {
+        if !self.positions.remove(&value) {
+            return; // not present
         }
 
-        if check_pos + 1 != self.gaps.len() {
-            // check for the gap agains our higher neighbor
-            let higher_neighbor = self.gaps[check_pos + 1];
-            let gap = higher_neighbor - value;
-            self.max_gap = self.max_gap.max(gap);
+        let left = self.positions.range(..value).next_back().copied();
+        let right = self.positions.range(value..).next().copied();
+        match (left, right) {
+            (Some(left), Some(right)) => {
+                self.drop_gap(value - left);
+                self.drop_gap(right - value);
+                self.bump_gap(right - left);
+            }
+            (Some(left), None) => self.drop_gap(value - left),
+            (None, Some(right)) => self.drop_gap(right - value),
+            (None, None) => {}
         }
     }
 }
@@ -83,7 +215,7 @@ mod test {
             gap_measurment.add(element);
         }
 
-        assert_eq!(gap_measurment.max_gap, expected_gap);
+        assert_eq!(gap_measurment.max_gap(), expected_gap);
     }
 
     #[test]
@@ -99,4 +231,67 @@ mod test {
     fn gap_measurment_ooo() {
         gap_measurment_fixture(vec![10, 5, 1], 5);
     }
+
+    #[test]
+    fn gap_measurment_remove_merges_neighboring_gaps() {
+        let mut gap_measurment = GapMeasurement::default();
+        for element in [1, 5, 6] {
+            gap_measurment.add(element);
+        }
+        assert_eq!(gap_measurment.max_gap(), 4); // 1 -> 5
+
+        gap_measurment.remove(5);
+        assert_eq!(gap_measurment.max_gap(), 5); // 1 -> 6, merged
+    }
+
+    #[test]
+    fn gap_measurment_remove_endpoint_shrinks_max() {
+        let mut gap_measurment = GapMeasurement::default();
+        for element in [1, 10] {
+            gap_measurment.add(element);
+        }
+        assert_eq!(gap_measurment.max_gap(), 9);
+
+        gap_measurment.remove(1);
+        assert_eq!(gap_measurment.max_gap(), 0); // only `10` left, no gaps
+    }
+
+    #[test]
+    fn heuristic_has_hysteresis_between_watermarks() {
+        let mut heuristic = MaxGapConvertHeuristic::new(10, 5);
+
+        heuristic.on_insert(CellCoordinate { row: 0, col: 0 });
+        heuristic.on_insert(CellCoordinate { row: 20, col: 0 });
+        assert!(heuristic.should_convert_to_sparse()); // gap of 20 >= high watermark
+
+        heuristic.on_remove(CellCoordinate { row: 20, col: 0 });
+        heuristic.on_insert(CellCoordinate { row: 7, col: 0 });
+        // gap of 7 is below the high watermark but above the low watermark, so a grid that
+        // was already sparse should stay sparse instead of thrashing back to dense.
+        assert!(heuristic.should_convert_to_sparse());
+
+        heuristic.on_remove(CellCoordinate { row: 7, col: 0 });
+        assert!(!heuristic.should_convert_to_sparse()); // back to a single point, no gap at all
+    }
+
+    #[test]
+    fn resync_discards_stale_state_and_rebuilds_from_the_given_coords() {
+        let mut heuristic = MaxGapConvertHeuristic::new(10, 5);
+
+        heuristic.on_insert(CellCoordinate { row: 0, col: 0 });
+        heuristic.on_insert(CellCoordinate { row: 20, col: 0 });
+        assert!(heuristic.should_convert_to_sparse()); // gap of 20 >= high watermark
+
+        // a bulk structural edit moves every coordinate at once rather than replaying
+        // on_remove/on_insert per cell; resync should reflect only what it's handed.
+        heuristic.resync(Box::new(
+            vec![
+                CellCoordinate { row: 0, col: 0 },
+                CellCoordinate { row: 1, col: 0 },
+            ]
+            .into_iter(),
+        ));
+        // still sparse from before, so the gap of 1 is measured against the low watermark.
+        assert!(!heuristic.should_convert_to_sparse());
+    }
 }
@@ -1,4 +1,7 @@
-use std::{collections::HashMap, fmt::Debug};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+};
 
 use crate::distance::ConvertHeuristic;
 
@@ -19,6 +22,10 @@ impl GridCell {
             data: s.as_ref().to_string(),
         }
     }
+
+    pub fn data(&self) -> &str {
+        &self.data
+    }
 }
 
 // CellCoordinate: Position of a cell in the grid to be rendered
@@ -27,6 +34,50 @@ pub struct CellCoordinate {
     pub row: usize,
     pub col: usize,
 }
+
+impl CellCoordinate {
+    // apply a signed offset to a usize coordinate, refusing to wrap below zero.
+    fn checked_offset(&self, d_row: i64, d_col: i64) -> Option<CellCoordinate> {
+        Some(CellCoordinate {
+            row: apply_checked_delta(self.row, d_row)?,
+            col: apply_checked_delta(self.col, d_col)?,
+        })
+    }
+
+    /// The up-to-four orthogonally adjacent coordinates (no diagonals), skipping any that
+    /// would fall below row/col zero rather than wrapping.
+    pub fn neighbors4(&self) -> Vec<CellCoordinate> {
+        [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .into_iter()
+            .filter_map(|(d_row, d_col)| self.checked_offset(d_row, d_col))
+            .collect()
+    }
+
+    /// The up-to-eight adjacent coordinates, including diagonals, skipping any that would
+    /// fall below row/col zero rather than wrapping.
+    pub fn neighbors8(&self) -> Vec<CellCoordinate> {
+        let mut out = Vec::with_capacity(8);
+        for d_row in -1i64..=1 {
+            for d_col in -1i64..=1 {
+                if d_row == 0 && d_col == 0 {
+                    continue;
+                }
+                if let Some(coord) = self.checked_offset(d_row, d_col) {
+                    out.push(coord);
+                }
+            }
+        }
+        out
+    }
+}
+
+fn apply_checked_delta(value: usize, delta: i64) -> Option<usize> {
+    if delta < 0 {
+        value.checked_sub((-delta) as usize)
+    } else {
+        value.checked_add(delta as usize)
+    }
+}
 ///
 /// Repersents the entire collection of cells. Depending on the state of the grid, distance between cells we may use a Sparse or Dense impl.
 /// This change is handled in the GridContainer
@@ -38,16 +89,190 @@ pub trait Grid: Debug {
 
     //fn coord_iter(&self) -> Box<dyn Iterator<Item = CellCoordinate>>;
     fn coord_iter<'a>(&'a self) -> Box<dyn Iterator<Item = CellCoordinate> + 'a>;
+
+    /// The (min, max) occupied coordinates, i.e. the smallest rectangle covering every
+    /// populated cell. `None` if the grid is empty.
+    fn bounds(&self) -> Option<(CellCoordinate, CellCoordinate)>;
+
+    /// Coordinates of occupied cells within the inclusive rectangle `top_left..=bottom_right`.
+    fn range_iter<'a>(
+        &'a self,
+        top_left: CellCoordinate,
+        bottom_right: CellCoordinate,
+    ) -> Box<dyn Iterator<Item = CellCoordinate> + 'a>;
+
+    /// Opens an empty row at `at`, shifting every cell with `row >= at` down by one, the way a
+    /// spreadsheet or terminal grid reflows content on a row insert. The default rewrites each
+    /// shifted coordinate via `remove`/`insert`; `DenseGrid` overrides this to splice its Vecs
+    /// directly instead.
+    fn insert_row(&mut self, at: usize) {
+        shift_coords(self, |c| c.row >= at, |c| {
+            Some(CellCoordinate { row: c.row + 1, col: c.col })
+        });
+    }
+
+    /// Removes row `at` and shifts every cell with `row > at` up by one to close the gap.
+    fn delete_row(&mut self, at: usize) {
+        shift_coords(self, |c| c.row >= at, |c| {
+            if c.row == at {
+                None
+            } else {
+                Some(CellCoordinate { row: c.row - 1, col: c.col })
+            }
+        });
+    }
+
+    /// Opens an empty column at `at`, shifting every cell with `col >= at` right by one.
+    fn insert_col(&mut self, at: usize) {
+        shift_coords(self, |c| c.col >= at, |c| {
+            Some(CellCoordinate { row: c.row, col: c.col + 1 })
+        });
+    }
+
+    /// Removes column `at` and shifts every cell with `col > at` left by one to close the gap.
+    fn delete_col(&mut self, at: usize) {
+        shift_coords(self, |c| c.col >= at, |c| {
+            if c.col == at {
+                None
+            } else {
+                Some(CellCoordinate { row: c.row, col: c.col - 1 })
+            }
+        });
+    }
+
+    /// The up-to-four orthogonally adjacent coordinates of `coord` that hold a `GridCell`.
+    fn neighbors4(&self, coord: &CellCoordinate) -> Vec<CellCoordinate> {
+        self.occupied_among(coord.neighbors4())
+    }
+
+    /// The up-to-eight adjacent coordinates (incl. diagonals) of `coord` that hold a `GridCell`.
+    fn neighbors8(&self, coord: &CellCoordinate) -> Vec<CellCoordinate> {
+        self.occupied_among(coord.neighbors8())
+    }
+
+    // filter `candidates` down to those actually occupied, via a single range_iter over the
+    // small bounding box the candidates fall in rather than a get_mut per candidate.
+    fn occupied_among(&self, candidates: Vec<CellCoordinate>) -> Vec<CellCoordinate> {
+        if candidates.is_empty() {
+            return candidates;
+        }
+        let top_left = CellCoordinate {
+            row: candidates.iter().map(|c| c.row).min().unwrap(),
+            col: candidates.iter().map(|c| c.col).min().unwrap(),
+        };
+        let bottom_right = CellCoordinate {
+            row: candidates.iter().map(|c| c.row).max().unwrap(),
+            col: candidates.iter().map(|c| c.col).max().unwrap(),
+        };
+        let present: HashSet<CellCoordinate> =
+            self.range_iter(top_left, bottom_right).collect();
+        candidates
+            .into_iter()
+            .filter(|coord| present.contains(coord))
+            .collect()
+    }
 }
 
 trait Convertable {
     fn should_convert(&self) -> bool;
 }
 
+// generic row/column shift: collects every coordinate `affected` picks out, removes them all
+// (so no shifted cell can clobber one not yet moved), then reinserts whatever `remap` maps it
+// to, dropping it if `remap` returns `None`. Used by the default `insert_row`/`delete_row`/
+// `insert_col`/`delete_col` impls; `DenseGrid` overrides those with a direct Vec splice instead.
+fn shift_coords<G: Grid + ?Sized>(
+    grid: &mut G,
+    affected: impl Fn(&CellCoordinate) -> bool,
+    remap: impl Fn(CellCoordinate) -> Option<CellCoordinate>,
+) {
+    let coords: Vec<CellCoordinate> = grid.coord_iter().filter(|c| affected(c)).collect();
+    let removed: Vec<(CellCoordinate, GridCell)> = coords
+        .into_iter()
+        .filter_map(|c| grid.remove(&c).map(|cell| (c, cell)))
+        .collect();
+    for (coord, cell) in removed {
+        if let Some(new_coord) = remap(coord) {
+            grid.insert(&new_coord, cell);
+        }
+    }
+}
+
+fn in_rect(coord: &CellCoordinate, top_left: &CellCoordinate, bottom_right: &CellCoordinate) -> bool {
+    coord.row >= top_left.row
+        && coord.row <= bottom_right.row
+        && coord.col >= top_left.col
+        && coord.col <= bottom_right.col
+}
+
+// recompute (min, max) occupied bounds from scratch, used after a removal shrinks the
+// bounding rectangle and we can't tell the new edge without rescanning.
+fn rescan_bounds<I: Iterator<Item = CellCoordinate>>(
+    it: I,
+) -> Option<(CellCoordinate, CellCoordinate)> {
+    it.fold(None, |acc, coord| match acc {
+        None => Some((coord, coord)),
+        Some((min, max)) => Some((
+            CellCoordinate {
+                row: min.row.min(coord.row),
+                col: min.col.min(coord.col),
+            },
+            CellCoordinate {
+                row: max.row.max(coord.row),
+                col: max.col.max(coord.col),
+            },
+        )),
+    })
+}
+
+// expand the running bounds to cover `coord`, used on every insert.
+fn expand_bounds(
+    bounds: &mut Option<(CellCoordinate, CellCoordinate)>,
+    coord: &CellCoordinate,
+) {
+    *bounds = Some(match *bounds {
+        None => (*coord, *coord),
+        Some((min, max)) => (
+            CellCoordinate {
+                row: min.row.min(coord.row),
+                col: min.col.min(coord.col),
+            },
+            CellCoordinate {
+                row: max.row.max(coord.row),
+                col: max.col.max(coord.col),
+            },
+        ),
+    });
+}
+
+// a removed coord might have been on the bounding rectangle's edge; if so the bounds need a
+// full rescan since we don't know the new edge without re-examining the remaining cells.
+fn shrink_bounds<I: Iterator<Item = CellCoordinate>>(
+    bounds: Option<(CellCoordinate, CellCoordinate)>,
+    removed: &CellCoordinate,
+    remaining: I,
+) -> Option<(CellCoordinate, CellCoordinate)> {
+    match bounds {
+        Some((min, max)) => {
+            let on_edge = removed.row == min.row
+                || removed.row == max.row
+                || removed.col == min.col
+                || removed.col == max.col;
+            if on_edge {
+                rescan_bounds(remaining)
+            } else {
+                bounds
+            }
+        }
+        None => None,
+    }
+}
+
 /// Repersenets the case where most of the grid is full of gaps (distance between cells). For instance: there is a occupied cell at (1, 1) and (1, 100)
 #[derive(Clone, Default, Debug)]
 struct SparseGrid {
     cols_rows: HashMap<CellCoordinate, GridCell>,
+    bounds: Option<(CellCoordinate, CellCoordinate)>,
 }
 
 impl Grid for SparseGrid {
@@ -56,15 +281,37 @@ impl Grid for SparseGrid {
     }
 
     fn remove(&mut self, coord: &CellCoordinate) -> Option<GridCell> {
-        self.cols_rows.remove(coord)
+        let removed = self.cols_rows.remove(coord);
+        if removed.is_some() {
+            self.bounds = shrink_bounds(self.bounds, coord, self.cols_rows.keys().copied());
+        }
+        removed
     }
 
     fn insert(&mut self, coord: &CellCoordinate, cell: GridCell) {
         self.cols_rows.insert(*coord, cell);
+        expand_bounds(&mut self.bounds, coord);
     }
     fn coord_iter<'a>(&'a self) -> Box<dyn Iterator<Item = CellCoordinate> + 'a> {
         Box::new(self.cols_rows.keys().map(|e| e.clone()))
     }
+
+    fn bounds(&self) -> Option<(CellCoordinate, CellCoordinate)> {
+        self.bounds
+    }
+
+    fn range_iter<'a>(
+        &'a self,
+        top_left: CellCoordinate,
+        bottom_right: CellCoordinate,
+    ) -> Box<dyn Iterator<Item = CellCoordinate> + 'a> {
+        Box::new(
+            self.cols_rows
+                .keys()
+                .filter(move |coord| in_rect(coord, &top_left, &bottom_right))
+                .copied(),
+        )
+    }
 }
 
 impl Into<DenseGrid> for SparseGrid {
@@ -81,6 +328,7 @@ impl Into<DenseGrid> for SparseGrid {
 #[derive(Clone, Default, Debug)]
 struct DenseGrid {
     cols_rows: Vec<Vec<Option<GridCell>>>,
+    bounds: Option<(CellCoordinate, CellCoordinate)>,
 }
 
 impl Grid for DenseGrid {
@@ -92,13 +340,19 @@ impl Grid for DenseGrid {
     }
 
     fn remove(&mut self, coord: &CellCoordinate) -> Option<GridCell> {
-        if let Some(rows) = self.cols_rows.get_mut(coord.col) {
+        let removed = if let Some(rows) = self.cols_rows.get_mut(coord.col) {
             if let Some(cell_ref) = rows.get_mut(coord.row) {
-                return std::mem::take(cell_ref);
+                std::mem::take(cell_ref)
+            } else {
+                None
             }
-            return None;
+        } else {
+            None
+        };
+        if removed.is_some() {
+            self.bounds = shrink_bounds(self.bounds, coord, self.coord_iter());
         }
-        return None;
+        removed
     }
 
     fn insert(&mut self, coord: &CellCoordinate, cell: GridCell) {
@@ -117,6 +371,41 @@ impl Grid for DenseGrid {
             }
         }
         // don't worry about the else case since we should not hit it if we re-size correctly
+        expand_bounds(&mut self.bounds, coord);
+    }
+
+    fn insert_row(&mut self, at: usize) {
+        for rows in &mut self.cols_rows {
+            if at <= rows.len() {
+                rows.insert(at, None);
+            }
+        }
+        self.bounds = rescan_bounds(self.coord_iter());
+    }
+
+    fn delete_row(&mut self, at: usize) {
+        for rows in &mut self.cols_rows {
+            if at < rows.len() {
+                rows.remove(at);
+            }
+        }
+        self.bounds = rescan_bounds(self.coord_iter());
+    }
+
+    fn insert_col(&mut self, at: usize) {
+        if at < self.cols_rows.len() {
+            self.cols_rows.insert(at, vec![]);
+        } else {
+            self.cols_rows.resize_with(at + 1, Vec::new);
+        }
+        self.bounds = rescan_bounds(self.coord_iter());
+    }
+
+    fn delete_col(&mut self, at: usize) {
+        if at < self.cols_rows.len() {
+            self.cols_rows.remove(at);
+        }
+        self.bounds = rescan_bounds(self.coord_iter());
     }
 
     fn coord_iter<'a>(&'a self) -> Box<dyn Iterator<Item = CellCoordinate> + 'a> {
@@ -131,6 +420,32 @@ impl Grid for DenseGrid {
             .flat_map(|opt| opt.into_iter());
         Box::new(iter)
     }
+
+    fn bounds(&self) -> Option<(CellCoordinate, CellCoordinate)> {
+        self.bounds
+    }
+
+    fn range_iter<'a>(
+        &'a self,
+        top_left: CellCoordinate,
+        bottom_right: CellCoordinate,
+    ) -> Box<dyn Iterator<Item = CellCoordinate> + 'a> {
+        if self.cols_rows.is_empty() || top_left.col >= self.cols_rows.len() {
+            return Box::new(std::iter::empty());
+        }
+        let col_lo = top_left.col;
+        let col_hi = bottom_right.col.min(self.cols_rows.len() - 1);
+        Box::new((col_lo..=col_hi).flat_map(move |col_idx| {
+            let rows = &self.cols_rows[col_idx];
+            let row_lo = top_left.row;
+            let row_hi = bottom_right.row.min(rows.len().saturating_sub(1));
+            (row_lo..=row_hi).filter_map(move |row_idx| {
+                rows.get(row_idx)
+                    .and_then(|c| c.as_ref())
+                    .map(|_| CellCoordinate { row: row_idx, col: col_idx })
+            })
+        }))
+    }
 }
 
 struct DenseGridIter<I> {
@@ -172,20 +487,173 @@ impl Into<SparseGrid> for DenseGrid {
     }
 }
 
+/// Repersents a read-optimized, compressed-sparse-row layout: `row_offsets` marks where each
+/// row's entries start/end in the parallel `col_indices`/`cells` arrays, with `col_indices`
+/// sorted ascending within a row. Good for large, mostly-static grids: no HashMap overhead and
+/// ordered iteration falls out for free, but `insert`/`remove` must splice all three arrays.
+#[derive(Clone, Default, Debug)]
+struct CompressedGrid {
+    row_offsets: Vec<usize>,
+    col_indices: Vec<usize>,
+    cells: Vec<GridCell>,
+    bounds: Option<(CellCoordinate, CellCoordinate)>,
+}
+
+impl CompressedGrid {
+    // make sure row_offsets has an entry for `row` and `row + 1`, carrying the previous
+    // row's end offset forward for any newly-opened, still-empty rows.
+    fn ensure_rows(&mut self, row: usize) {
+        if self.row_offsets.is_empty() {
+            self.row_offsets.push(0);
+        }
+        while self.row_offsets.len() <= row + 1 {
+            let last = *self.row_offsets.last().unwrap();
+            self.row_offsets.push(last);
+        }
+    }
+
+    fn row_bounds(&self, row: usize) -> Option<(usize, usize)> {
+        if row + 1 >= self.row_offsets.len() {
+            return None;
+        }
+        Some((self.row_offsets[row], self.row_offsets[row + 1]))
+    }
+}
+
+impl Grid for CompressedGrid {
+    fn get_mut(&mut self, coord: &CellCoordinate) -> Option<&mut GridCell> {
+        let (start, end) = self.row_bounds(coord.row)?;
+        let pos = self.col_indices[start..end].binary_search(&coord.col).ok()?;
+        self.cells.get_mut(start + pos)
+    }
+
+    fn remove(&mut self, coord: &CellCoordinate) -> Option<GridCell> {
+        let (start, end) = self.row_bounds(coord.row)?;
+        let pos = self.col_indices[start..end].binary_search(&coord.col).ok()?;
+        let idx = start + pos;
+        self.col_indices.remove(idx);
+        let cell = self.cells.remove(idx);
+        for offset in &mut self.row_offsets[coord.row + 1..] {
+            *offset -= 1;
+        }
+        self.bounds = shrink_bounds(self.bounds, coord, self.coord_iter());
+        Some(cell)
+    }
+
+    fn insert(&mut self, coord: &CellCoordinate, cell: GridCell) {
+        self.ensure_rows(coord.row);
+        let (start, end) = self.row_bounds(coord.row).unwrap();
+        match self.col_indices[start..end].binary_search(&coord.col) {
+            Ok(pos) => {
+                self.cells[start + pos] = cell;
+            }
+            Err(pos) => {
+                let idx = start + pos;
+                self.col_indices.insert(idx, coord.col);
+                self.cells.insert(idx, cell);
+                for offset in &mut self.row_offsets[coord.row + 1..] {
+                    *offset += 1;
+                }
+            }
+        }
+        expand_bounds(&mut self.bounds, coord);
+    }
+
+    fn coord_iter<'a>(&'a self) -> Box<dyn Iterator<Item = CellCoordinate> + 'a> {
+        let num_rows = self.row_offsets.len().saturating_sub(1);
+        Box::new((0..num_rows).flat_map(move |row| {
+            let (start, end) = self.row_bounds(row).unwrap();
+            self.col_indices[start..end]
+                .iter()
+                .map(move |&col| CellCoordinate { row, col })
+        }))
+    }
+
+    fn bounds(&self) -> Option<(CellCoordinate, CellCoordinate)> {
+        self.bounds
+    }
+
+    fn range_iter<'a>(
+        &'a self,
+        top_left: CellCoordinate,
+        bottom_right: CellCoordinate,
+    ) -> Box<dyn Iterator<Item = CellCoordinate> + 'a> {
+        let num_rows = self.row_offsets.len().saturating_sub(1);
+        let row_hi = bottom_right.row.min(num_rows.saturating_sub(1));
+        if num_rows == 0 || top_left.row >= num_rows {
+            return Box::new(std::iter::empty());
+        }
+        Box::new((top_left.row..=row_hi).flat_map(move |row| {
+            let (start, end) = self.row_bounds(row).unwrap();
+            let cols = &self.col_indices[start..end];
+            // binary-search the row's sorted column slice down to the requested window.
+            let lo = cols.partition_point(|&c| c < top_left.col);
+            let hi = cols.partition_point(|&c| c <= bottom_right.col);
+            cols[lo..hi].iter().map(move |&col| CellCoordinate { row, col })
+        }))
+    }
+}
+
+impl From<CompressedGrid> for SparseGrid {
+    fn from(val: CompressedGrid) -> Self {
+        let mut sparse = SparseGrid::default();
+        let num_rows = val.row_offsets.len().saturating_sub(1);
+        for row in 0..num_rows {
+            let (start, end) = val.row_bounds(row).unwrap();
+            for (col, cell) in val.col_indices[start..end]
+                .iter()
+                .zip(val.cells[start..end].iter())
+            {
+                sparse.insert(&CellCoordinate { row, col: *col }, cell.clone());
+            }
+        }
+        sparse
+    }
+}
+
+impl From<SparseGrid> for CompressedGrid {
+    fn from(val: SparseGrid) -> Self {
+        let mut coords: Vec<(CellCoordinate, GridCell)> = val.cols_rows.into_iter().collect();
+        coords.sort_by_key(|(coord, _)| *coord);
+
+        let mut compressed = CompressedGrid::default();
+        for (coord, cell) in coords {
+            compressed.insert(&coord, cell);
+        }
+        compressed
+    }
+}
+
 #[derive(Clone, Debug)]
 enum SwappingGrid {
     Dense(DenseGrid),
     Sparse(SparseGrid),
+    Compressed(CompressedGrid),
 }
 
 impl SwappingGrid {
-    fn swap(self) -> Self {
+    // collapse any variant down to the HashMap-backed representation, used as the common
+    // ground to hop between Dense and Compressed.
+    fn into_sparse(self) -> SparseGrid {
         match self {
-            Self::Dense(g) => Self::Sparse(g.into()),
-            Self::Sparse(g) => Self::Dense(g.into()),
+            Self::Dense(g) => g.into(),
+            Self::Sparse(g) => g,
+            Self::Compressed(g) => g.into(),
         }
     }
 
+    fn into_dense(self) -> Self {
+        Self::Dense(self.into_sparse().into())
+    }
+
+    fn into_sparse_variant(self) -> Self {
+        Self::Sparse(self.into_sparse())
+    }
+
+    fn into_compressed(self) -> Self {
+        Self::Compressed(self.into_sparse().into())
+    }
+
     fn default() -> Self {
         Self::Dense(DenseGrid::default())
     }
@@ -197,13 +665,24 @@ impl SwappingGrid {
 pub struct GridContainer {
     grid: SwappingGrid,
     convert_heuristic: Box<dyn ConvertHeuristic>,
+    // number of consecutive inserts/removes that left the grid sparse and stable; once this
+    // crosses COMPRESS_AFTER_STABLE_OPS we treat the grid as "rarely mutated" and compress it.
+    stable_sparse_ops: usize,
 }
 
+// after this many back-to-back ops that leave a grid sparse (rather than swapping to/from
+// dense), we assume it's read-mostly and switch to the more compact CSR layout.
+const COMPRESS_AFTER_STABLE_OPS: usize = 32;
+// if a compressed grid gets mutated this many times, it's not "rarely mutated" after all, so
+// drop back down to the HashMap representation rather than keep paying the splice cost.
+const DECOMPRESS_AFTER_MUTATIONS: usize = 4;
+
 impl GridContainer {
     pub fn new(convert_heuristic: Box<dyn ConvertHeuristic>) -> Self {
         Self {
             grid: SwappingGrid::default(),
             convert_heuristic,
+            stable_sparse_ops: 0,
         }
     }
 
@@ -213,6 +692,59 @@ impl GridContainer {
             _ => false,
         }
     }
+
+    pub fn is_compressed(&self) -> bool {
+        matches!(self.grid, SwappingGrid::Compressed(_))
+    }
+
+    // re-evaluate dense/sparse/compressed placement after a mutation.
+    fn rebalance(&mut self) {
+        let should_be_sparse = self.convert_heuristic.should_convert_to_sparse();
+        let is_dense = matches!(self.grid, SwappingGrid::Dense(_));
+
+        if should_be_sparse && is_dense {
+            let to_swap = std::mem::replace(&mut self.grid, SwappingGrid::default());
+            self.grid = to_swap.into_sparse_variant();
+            self.stable_sparse_ops = 0;
+            return;
+        }
+        if !should_be_sparse && !is_dense {
+            let to_swap = std::mem::replace(&mut self.grid, SwappingGrid::default());
+            self.grid = to_swap.into_dense();
+            self.stable_sparse_ops = 0;
+            return;
+        }
+
+        match self.grid {
+            SwappingGrid::Sparse(_) => {
+                self.stable_sparse_ops += 1;
+                if self.stable_sparse_ops >= COMPRESS_AFTER_STABLE_OPS {
+                    let to_swap = std::mem::replace(&mut self.grid, SwappingGrid::default());
+                    self.grid = to_swap.into_compressed();
+                    self.stable_sparse_ops = 0;
+                }
+            }
+            SwappingGrid::Compressed(_) => {
+                self.stable_sparse_ops += 1;
+                if self.stable_sparse_ops >= DECOMPRESS_AFTER_MUTATIONS {
+                    let to_swap = std::mem::replace(&mut self.grid, SwappingGrid::default());
+                    self.grid = to_swap.into_sparse_variant();
+                    self.stable_sparse_ops = 0;
+                }
+            }
+            SwappingGrid::Dense(_) => {}
+        }
+    }
+
+    // after a bulk structural edit (row/column insert/delete) every shifted coordinate has
+    // already moved directly on the underlying grid, bypassing `insert`/`remove` and their
+    // per-cell heuristic hooks. Resync the heuristic from a single fresh scan instead, then
+    // rebalance once rather than once per shifted cell.
+    fn after_structural_edit(&mut self) {
+        let coords: Vec<CellCoordinate> = self.coord_iter().collect();
+        self.convert_heuristic.resync(Box::new(coords.into_iter()));
+        self.rebalance();
+    }
 }
 
 impl Grid for GridContainer {
@@ -220,59 +752,97 @@ impl Grid for GridContainer {
         match self.grid {
             SwappingGrid::Dense(ref mut g) => g.get_mut(coord),
             SwappingGrid::Sparse(ref mut g) => g.get_mut(coord),
+            SwappingGrid::Compressed(ref mut g) => g.get_mut(coord),
         }
     }
 
     fn remove(&mut self, coord: &CellCoordinate) -> Option<GridCell> {
-        let (return_opt, should_be_sparse, is_sparse) = match self.grid {
-            SwappingGrid::Dense(ref mut g) => {
-                let ret = g.remove(coord);
-                let it = g.coord_iter();
-                let convert = self.convert_heuristic.convert_to_sparse(it);
-                (ret, convert, false)
-            }
-            SwappingGrid::Sparse(ref mut g) => {
-                let ret = g.remove(coord);
-                let it = g.coord_iter();
-                let convert = self.convert_heuristic.convert_to_sparse(it);
-                (ret, convert, true)
-            }
+        let return_opt = match self.grid {
+            SwappingGrid::Dense(ref mut g) => g.remove(coord),
+            SwappingGrid::Sparse(ref mut g) => g.remove(coord),
+            SwappingGrid::Compressed(ref mut g) => g.remove(coord),
         };
 
-        if (should_be_sparse && !is_sparse) || (!should_be_sparse && is_sparse) {
-            let to_swap = std::mem::replace(&mut self.grid, SwappingGrid::default());
-            self.grid = to_swap.swap();
+        if return_opt.is_some() {
+            self.convert_heuristic.on_remove(*coord);
         }
+        self.rebalance();
 
-        return return_opt;
+        return_opt
     }
 
     fn insert(&mut self, coord: &CellCoordinate, cell: GridCell) {
-        let (should_be_sparse, is_sparse) = match self.grid {
-            SwappingGrid::Dense(ref mut g) => {
-                g.insert(coord, cell);
-                let it = g.coord_iter();
-                let convert = self.convert_heuristic.convert_to_sparse(it);
-                (convert, false)
-            }
-            SwappingGrid::Sparse(ref mut g) => {
-                g.insert(coord, cell);
-                let it = g.coord_iter();
-                let convert = self.convert_heuristic.convert_to_sparse(it);
-                (convert, true)
-            }
-        };
+        match self.grid {
+            SwappingGrid::Dense(ref mut g) => g.insert(coord, cell),
+            SwappingGrid::Sparse(ref mut g) => g.insert(coord, cell),
+            SwappingGrid::Compressed(ref mut g) => g.insert(coord, cell),
+        }
 
-        if (should_be_sparse && !is_sparse) || (!should_be_sparse && is_sparse) {
-            let to_swap = std::mem::replace(&mut self.grid, SwappingGrid::default());
-            self.grid = to_swap.swap();
+        self.convert_heuristic.on_insert(*coord);
+        self.rebalance();
+    }
+
+    fn insert_row(&mut self, at: usize) {
+        match self.grid {
+            SwappingGrid::Dense(ref mut g) => g.insert_row(at),
+            SwappingGrid::Sparse(ref mut g) => g.insert_row(at),
+            SwappingGrid::Compressed(ref mut g) => g.insert_row(at),
+        }
+        self.after_structural_edit();
+    }
+
+    fn delete_row(&mut self, at: usize) {
+        match self.grid {
+            SwappingGrid::Dense(ref mut g) => g.delete_row(at),
+            SwappingGrid::Sparse(ref mut g) => g.delete_row(at),
+            SwappingGrid::Compressed(ref mut g) => g.delete_row(at),
         }
+        self.after_structural_edit();
+    }
+
+    fn insert_col(&mut self, at: usize) {
+        match self.grid {
+            SwappingGrid::Dense(ref mut g) => g.insert_col(at),
+            SwappingGrid::Sparse(ref mut g) => g.insert_col(at),
+            SwappingGrid::Compressed(ref mut g) => g.insert_col(at),
+        }
+        self.after_structural_edit();
+    }
+
+    fn delete_col(&mut self, at: usize) {
+        match self.grid {
+            SwappingGrid::Dense(ref mut g) => g.delete_col(at),
+            SwappingGrid::Sparse(ref mut g) => g.delete_col(at),
+            SwappingGrid::Compressed(ref mut g) => g.delete_col(at),
+        }
+        self.after_structural_edit();
     }
 
     fn coord_iter<'a>(&'a self) -> Box<dyn Iterator<Item = CellCoordinate> + 'a> {
         match self.grid {
             SwappingGrid::Dense(ref g) => g.coord_iter(),
             SwappingGrid::Sparse(ref g) => g.coord_iter(),
+            SwappingGrid::Compressed(ref g) => g.coord_iter(),
+        }
+    }
+
+    fn bounds(&self) -> Option<(CellCoordinate, CellCoordinate)> {
+        match self.grid {
+            SwappingGrid::Dense(ref g) => g.bounds(),
+            SwappingGrid::Sparse(ref g) => g.bounds(),
+            SwappingGrid::Compressed(ref g) => g.bounds(),
+        }
+    }
+
+    fn range_iter<'a>(
+        &'a self,
+        top_left: CellCoordinate,
+        bottom_right: CellCoordinate,
+    ) -> Box<dyn Iterator<Item = CellCoordinate> + 'a> {
+        match self.grid {
+            SwappingGrid::Dense(ref g) => g.range_iter(top_left, bottom_right),
+            SwappingGrid::Sparse(ref g) => g.range_iter(top_left, bottom_right),
+            SwappingGrid::Compressed(ref g) => g.range_iter(top_left, bottom_right),
         }
     }
 }
@@ -337,6 +907,11 @@ mod tests {
         can_insert_and_get_and_remove(SparseGrid::default());
     }
 
+    #[test]
+    fn compressed_can_insert_and_get() {
+        can_insert_and_get_and_remove(CompressedGrid::default());
+    }
+
     #[test]
     fn sparse_can_iter() {
         can_iter(SparseGrid::default())
@@ -345,4 +920,275 @@ mod tests {
     fn dense_can_iter() {
         can_iter(DenseGrid::default())
     }
+    #[test]
+    fn compressed_can_iter() {
+        can_iter(CompressedGrid::default())
+    }
+
+    #[test]
+    fn compressed_iter_is_already_sorted() {
+        let mut g = CompressedGrid::default();
+        g.insert(&CellCoordinate { row: 2, col: 5 }, GridCell::new("c"));
+        g.insert(&CellCoordinate { row: 0, col: 3 }, GridCell::new("a"));
+        g.insert(&CellCoordinate { row: 0, col: 1 }, GridCell::new("b"));
+
+        let coords = g.coord_iter().collect::<Vec<_>>();
+        assert_eq!(
+            coords,
+            vec![
+                CellCoordinate { row: 0, col: 1 },
+                CellCoordinate { row: 0, col: 3 },
+                CellCoordinate { row: 2, col: 5 },
+            ]
+        );
+    }
+
+    fn can_track_bounds<G: Grid + Debug>(mut g: G) {
+        assert_eq!(g.bounds(), None);
+
+        g.insert(&CellCoordinate { row: 3, col: 7 }, GridCell::new("a"));
+        g.insert(&CellCoordinate { row: 1, col: 9 }, GridCell::new("b"));
+        g.insert(&CellCoordinate { row: 5, col: 2 }, GridCell::new("c"));
+
+        assert_eq!(
+            g.bounds(),
+            Some((
+                CellCoordinate { row: 1, col: 2 },
+                CellCoordinate { row: 5, col: 9 }
+            ))
+        );
+
+        // removing an interior point doesn't change the bounding rectangle
+        g.remove(&CellCoordinate { row: 3, col: 7 });
+        assert_eq!(
+            g.bounds(),
+            Some((
+                CellCoordinate { row: 1, col: 2 },
+                CellCoordinate { row: 5, col: 9 }
+            ))
+        );
+
+        // removing an edge point shrinks the bounding rectangle
+        g.remove(&CellCoordinate { row: 5, col: 2 });
+        assert_eq!(
+            g.bounds(),
+            Some((
+                CellCoordinate { row: 1, col: 9 },
+                CellCoordinate { row: 1, col: 9 }
+            ))
+        );
+    }
+
+    fn can_range_iter<G: Grid + Debug>(mut g: G) {
+        g.insert(&CellCoordinate { row: 0, col: 0 }, GridCell::new("a"));
+        g.insert(&CellCoordinate { row: 1, col: 1 }, GridCell::new("b"));
+        g.insert(&CellCoordinate { row: 2, col: 2 }, GridCell::new("c"));
+        g.insert(&CellCoordinate { row: 5, col: 5 }, GridCell::new("d"));
+
+        let mut coords = g
+            .range_iter(
+                CellCoordinate { row: 1, col: 1 },
+                CellCoordinate { row: 2, col: 2 },
+            )
+            .collect::<Vec<_>>();
+        coords.sort();
+        assert_eq!(
+            coords,
+            vec![
+                CellCoordinate { row: 1, col: 1 },
+                CellCoordinate { row: 2, col: 2 },
+            ]
+        );
+    }
+
+    #[test]
+    fn dense_can_track_bounds() {
+        can_track_bounds(DenseGrid::default());
+    }
+    #[test]
+    fn sparse_can_track_bounds() {
+        can_track_bounds(SparseGrid::default());
+    }
+    #[test]
+    fn compressed_can_track_bounds() {
+        can_track_bounds(CompressedGrid::default());
+    }
+
+    #[test]
+    fn dense_can_range_iter() {
+        can_range_iter(DenseGrid::default());
+    }
+    #[test]
+    fn sparse_can_range_iter() {
+        can_range_iter(SparseGrid::default());
+    }
+    #[test]
+    fn compressed_can_range_iter() {
+        can_range_iter(CompressedGrid::default());
+    }
+
+    #[test]
+    fn coordinate_neighbors4_skips_negative() {
+        let origin = CellCoordinate { row: 0, col: 0 };
+        let mut neighbors = origin.neighbors4();
+        neighbors.sort();
+        assert_eq!(
+            neighbors,
+            vec![
+                CellCoordinate { row: 0, col: 1 },
+                CellCoordinate { row: 1, col: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn coordinate_neighbors8_skips_negative() {
+        let origin = CellCoordinate { row: 0, col: 0 };
+        let mut neighbors = origin.neighbors8();
+        neighbors.sort();
+        assert_eq!(
+            neighbors,
+            vec![
+                CellCoordinate { row: 0, col: 1 },
+                CellCoordinate { row: 1, col: 0 },
+                CellCoordinate { row: 1, col: 1 },
+            ]
+        );
+    }
+
+    fn can_query_occupied_neighbors<G: Grid + Debug>(mut g: G) {
+        let center = CellCoordinate { row: 5, col: 5 };
+        g.insert(&center, GridCell::new("center"));
+        g.insert(&CellCoordinate { row: 4, col: 5 }, GridCell::new("up"));
+        g.insert(&CellCoordinate { row: 5, col: 6 }, GridCell::new("right"));
+        g.insert(&CellCoordinate { row: 4, col: 4 }, GridCell::new("diag"));
+
+        let mut n4 = g.neighbors4(&center);
+        n4.sort();
+        assert_eq!(
+            n4,
+            vec![
+                CellCoordinate { row: 4, col: 5 },
+                CellCoordinate { row: 5, col: 6 },
+            ]
+        );
+
+        let mut n8 = g.neighbors8(&center);
+        n8.sort();
+        assert_eq!(
+            n8,
+            vec![
+                CellCoordinate { row: 4, col: 4 },
+                CellCoordinate { row: 4, col: 5 },
+                CellCoordinate { row: 5, col: 6 },
+            ]
+        );
+    }
+
+    #[test]
+    fn dense_can_query_occupied_neighbors() {
+        can_query_occupied_neighbors(DenseGrid::default());
+    }
+    #[test]
+    fn sparse_can_query_occupied_neighbors() {
+        can_query_occupied_neighbors(SparseGrid::default());
+    }
+    #[test]
+    fn compressed_can_query_occupied_neighbors() {
+        can_query_occupied_neighbors(CompressedGrid::default());
+    }
+
+    fn can_insert_and_delete_rows<G: Grid + Debug>(mut g: G) {
+        g.insert(&CellCoordinate { row: 0, col: 0 }, GridCell::new("a"));
+        g.insert(&CellCoordinate { row: 1, col: 0 }, GridCell::new("b"));
+
+        g.insert_row(1);
+        assert_eq!(g.get_mut(&CellCoordinate { row: 0, col: 0 }).map(|c| c.data()), Some("a"));
+        assert_eq!(g.get_mut(&CellCoordinate { row: 1, col: 0 }), None);
+        assert_eq!(g.get_mut(&CellCoordinate { row: 2, col: 0 }).map(|c| c.data()), Some("b"));
+
+        g.delete_row(1);
+        assert_eq!(g.get_mut(&CellCoordinate { row: 0, col: 0 }).map(|c| c.data()), Some("a"));
+        assert_eq!(g.get_mut(&CellCoordinate { row: 1, col: 0 }).map(|c| c.data()), Some("b"));
+    }
+
+    fn can_insert_and_delete_cols<G: Grid + Debug>(mut g: G) {
+        g.insert(&CellCoordinate { row: 0, col: 0 }, GridCell::new("a"));
+        g.insert(&CellCoordinate { row: 0, col: 1 }, GridCell::new("b"));
+
+        g.insert_col(1);
+        assert_eq!(g.get_mut(&CellCoordinate { row: 0, col: 0 }).map(|c| c.data()), Some("a"));
+        assert_eq!(g.get_mut(&CellCoordinate { row: 0, col: 1 }), None);
+        assert_eq!(g.get_mut(&CellCoordinate { row: 0, col: 2 }).map(|c| c.data()), Some("b"));
+
+        g.delete_col(1);
+        assert_eq!(g.get_mut(&CellCoordinate { row: 0, col: 0 }).map(|c| c.data()), Some("a"));
+        assert_eq!(g.get_mut(&CellCoordinate { row: 0, col: 1 }).map(|c| c.data()), Some("b"));
+    }
+
+    #[test]
+    fn dense_can_insert_and_delete_rows() {
+        can_insert_and_delete_rows(DenseGrid::default());
+    }
+    #[test]
+    fn sparse_can_insert_and_delete_rows() {
+        can_insert_and_delete_rows(SparseGrid::default());
+    }
+    #[test]
+    fn compressed_can_insert_and_delete_rows() {
+        can_insert_and_delete_rows(CompressedGrid::default());
+    }
+
+    #[test]
+    fn dense_can_insert_and_delete_cols() {
+        can_insert_and_delete_cols(DenseGrid::default());
+    }
+    #[test]
+    fn sparse_can_insert_and_delete_cols() {
+        can_insert_and_delete_cols(SparseGrid::default());
+    }
+    #[test]
+    fn compressed_can_insert_and_delete_cols() {
+        can_insert_and_delete_cols(CompressedGrid::default());
+    }
+
+    #[test]
+    fn delete_row_drops_cells_on_the_deleted_row() {
+        let mut g = SparseGrid::default();
+        g.insert(&CellCoordinate { row: 0, col: 0 }, GridCell::new("a"));
+        g.insert(&CellCoordinate { row: 1, col: 0 }, GridCell::new("b"));
+        g.insert(&CellCoordinate { row: 2, col: 0 }, GridCell::new("c"));
+
+        g.delete_row(1);
+
+        assert_eq!(g.get_mut(&CellCoordinate { row: 0, col: 0 }).map(|c| c.data()), Some("a"));
+        assert_eq!(g.get_mut(&CellCoordinate { row: 1, col: 0 }).map(|c| c.data()), Some("c"));
+        let mut coords = g.coord_iter().collect::<Vec<_>>();
+        coords.sort();
+        assert_eq!(
+            coords,
+            vec![
+                CellCoordinate { row: 0, col: 0 },
+                CellCoordinate { row: 1, col: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn structural_edit_on_grid_container_shifts_cells_and_stays_balanced() {
+        use crate::distance::MaxGapConvertHeuristic;
+
+        let mut g = GridContainer::new(Box::new(MaxGapConvertHeuristic::new(10, 5)));
+        for row in 0..3 {
+            g.insert(&CellCoordinate { row, col: 0 }, GridCell::new("x"));
+        }
+
+        g.insert_row(1);
+
+        assert_eq!(g.get_mut(&CellCoordinate { row: 0, col: 0 }).map(|c| c.data()), Some("x"));
+        assert_eq!(g.get_mut(&CellCoordinate { row: 1, col: 0 }), None);
+        assert_eq!(g.get_mut(&CellCoordinate { row: 3, col: 0 }).map(|c| c.data()), Some("x"));
+        // rows 0, 2, 3 leave a max gap of 2, well under the high watermark: still dense.
+        assert!(!g.is_sparse() && !g.is_compressed());
+    }
 }
@@ -0,0 +1,390 @@
+// Support for cells that span multiple coordinates (merged cells), spreadsheet-style.
+
+use std::collections::HashMap;
+
+use crate::grid::{CellCoordinate, Grid, GridCell};
+
+/// Describes the set of coordinates a single logical cell spans when anchored at one of them
+/// (e.g. the top-left of a merged block).
+pub trait GridShape {
+    fn covered_coords(&self, anchor: &CellCoordinate) -> Vec<CellCoordinate>;
+}
+
+/// A rectangular block of `rows` x `cols` cells anchored at its top-left coordinate.
+#[derive(Clone, Copy, Debug)]
+pub struct RectShape {
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl GridShape for RectShape {
+    fn covered_coords(&self, anchor: &CellCoordinate) -> Vec<CellCoordinate> {
+        (0..self.rows)
+            .flat_map(|d_row| {
+                (0..self.cols).map(move |d_col| CellCoordinate {
+                    row: anchor.row + d_row,
+                    col: anchor.col + d_col,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Wraps a `Grid` with support for merged cells: a single logical value can be registered
+/// across every coordinate a `GridShape` covers, anchored at one of them. The value itself is
+/// only ever stored at the anchor in the wrapped grid; every other covered coordinate is just
+/// a back-reference, so `get_mut`/`remove` resolve to (and clear) the whole span, while
+/// `coord_iter`/`bounds`/`range_iter` fall through to the inner grid and naturally see only
+/// the anchor.
+#[derive(Debug)]
+pub struct MergedGrid<G> {
+    inner: G,
+    // covered coordinate -> anchor coordinate (an anchor maps to itself)
+    anchor_of: HashMap<CellCoordinate, CellCoordinate>,
+    // anchor coordinate -> every coordinate its shape covers
+    span_of: HashMap<CellCoordinate, Vec<CellCoordinate>>,
+}
+
+impl<G: Grid> MergedGrid<G> {
+    pub fn new(inner: G) -> Self {
+        Self {
+            inner,
+            anchor_of: HashMap::new(),
+            span_of: HashMap::new(),
+        }
+    }
+
+    /// Registers `cell` at `anchor`, covering every coordinate `shape` reports for it. A
+    /// `get_mut`/`remove` at any covered coordinate resolves to this same logical cell.
+    pub fn insert_shape(&mut self, anchor: &CellCoordinate, shape: &dyn GridShape, cell: GridCell) {
+        let covered = shape.covered_coords(anchor);
+        self.inner.insert(anchor, cell);
+        for coord in &covered {
+            self.anchor_of.insert(*coord, *anchor);
+        }
+        self.span_of.insert(*anchor, covered);
+    }
+
+    fn resolve(&self, coord: &CellCoordinate) -> CellCoordinate {
+        self.anchor_of.get(coord).copied().unwrap_or(*coord)
+    }
+
+    // drop whatever span `anchor` owns (if any), unlinking every coordinate it covered.
+    fn clear_span(&mut self, anchor: &CellCoordinate) {
+        if let Some(covered) = self.span_of.remove(anchor) {
+            for member in &covered {
+                self.anchor_of.remove(member);
+            }
+        } else {
+            self.anchor_of.remove(anchor);
+        }
+    }
+
+    // rewrite every tracked coordinate (both keys and values) through `remap`, dropping an
+    // entry entirely if `remap` says its coordinate no longer exists. Used to keep the span
+    // back-references in step with a bulk row/column insert/delete on the inner grid.
+    fn remap_coords(&mut self, remap: impl Fn(CellCoordinate) -> Option<CellCoordinate>) {
+        self.anchor_of = std::mem::take(&mut self.anchor_of)
+            .into_iter()
+            .filter_map(|(covered, anchor)| Some((remap(covered)?, remap(anchor)?)))
+            .collect();
+
+        self.span_of = std::mem::take(&mut self.span_of)
+            .into_iter()
+            .filter_map(|(anchor, covered)| {
+                let anchor = remap(anchor)?;
+                let covered: Vec<CellCoordinate> = covered.into_iter().filter_map(&remap).collect();
+                if covered.is_empty() {
+                    None
+                } else {
+                    Some((anchor, covered))
+                }
+            })
+            .collect();
+    }
+}
+
+impl<G: Grid> Grid for MergedGrid<G> {
+    fn get_mut(&mut self, coord: &CellCoordinate) -> Option<&mut GridCell> {
+        let anchor = self.resolve(coord);
+        self.inner.get_mut(&anchor)
+    }
+
+    fn remove(&mut self, coord: &CellCoordinate) -> Option<GridCell> {
+        let anchor = self.resolve(coord);
+        self.clear_span(&anchor);
+        self.inner.remove(&anchor)
+    }
+
+    fn insert(&mut self, coord: &CellCoordinate, cell: GridCell) {
+        // a plain, unshaped insert always claims its own coordinate as a lone anchor; drop
+        // any span (this cell's own, or one it was merged into) that would otherwise leave
+        // stale back-references pointing at this spot. If `coord` wasn't the span's anchor,
+        // the anchor's cell is now orphaned in `inner` (nothing in `anchor_of`/`span_of`
+        // points at it any more) and must be removed rather than left to resurface via
+        // `coord_iter`/`bounds`/a direct `get_mut(&anchor)`.
+        let anchor = self.resolve(coord);
+        self.clear_span(&anchor);
+        if anchor != *coord {
+            self.inner.remove(&anchor);
+        }
+        self.inner.insert(coord, cell);
+    }
+
+    // keep the anchor/span back-references in lockstep with whichever coordinates the inner
+    // grid actually shifted, so a span doesn't end up pointing at a coordinate that moved out
+    // from under it.
+    fn insert_row(&mut self, at: usize) {
+        self.inner.insert_row(at);
+        self.remap_coords(|c| {
+            Some(if c.row >= at {
+                CellCoordinate { row: c.row + 1, col: c.col }
+            } else {
+                c
+            })
+        });
+    }
+
+    fn delete_row(&mut self, at: usize) {
+        self.inner.delete_row(at);
+        self.remap_coords(|c| {
+            if c.row == at {
+                None
+            } else if c.row > at {
+                Some(CellCoordinate { row: c.row - 1, col: c.col })
+            } else {
+                Some(c)
+            }
+        });
+    }
+
+    fn insert_col(&mut self, at: usize) {
+        self.inner.insert_col(at);
+        self.remap_coords(|c| {
+            Some(if c.col >= at {
+                CellCoordinate { row: c.row, col: c.col + 1 }
+            } else {
+                c
+            })
+        });
+    }
+
+    fn delete_col(&mut self, at: usize) {
+        self.inner.delete_col(at);
+        self.remap_coords(|c| {
+            if c.col == at {
+                None
+            } else if c.col > at {
+                Some(CellCoordinate { row: c.row, col: c.col - 1 })
+            } else {
+                Some(c)
+            }
+        });
+    }
+
+    fn coord_iter<'a>(&'a self) -> Box<dyn Iterator<Item = CellCoordinate> + 'a> {
+        self.inner.coord_iter()
+    }
+
+    fn bounds(&self) -> Option<(CellCoordinate, CellCoordinate)> {
+        self.inner.bounds()
+    }
+
+    fn range_iter<'a>(
+        &'a self,
+        top_left: CellCoordinate,
+        bottom_right: CellCoordinate,
+    ) -> Box<dyn Iterator<Item = CellCoordinate> + 'a> {
+        self.inner.range_iter(top_left, bottom_right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distance::MaxGapConvertHeuristic;
+    use crate::grid::GridContainer;
+
+    fn merged() -> MergedGrid<GridContainer> {
+        MergedGrid::new(GridContainer::new(Box::new(MaxGapConvertHeuristic::new(10, 5))))
+    }
+
+    #[test]
+    fn get_mut_resolves_covered_coords_to_the_anchor() {
+        let mut g = merged();
+        let anchor = CellCoordinate { row: 1, col: 1 };
+        g.insert_shape(&anchor, &RectShape { rows: 2, cols: 2 }, GridCell::new("merged"));
+
+        for coord in [
+            CellCoordinate { row: 1, col: 1 },
+            CellCoordinate { row: 1, col: 2 },
+            CellCoordinate { row: 2, col: 1 },
+            CellCoordinate { row: 2, col: 2 },
+        ] {
+            assert_eq!(g.get_mut(&coord).map(|c| c.data().to_string()), Some("merged".to_string()));
+        }
+    }
+
+    #[test]
+    fn remove_at_any_covered_coord_clears_the_whole_span() {
+        let mut g = merged();
+        let anchor = CellCoordinate { row: 0, col: 0 };
+        g.insert_shape(&anchor, &RectShape { rows: 2, cols: 2 }, GridCell::new("merged"));
+
+        g.remove(&CellCoordinate { row: 1, col: 1 });
+
+        for coord in [
+            CellCoordinate { row: 0, col: 0 },
+            CellCoordinate { row: 0, col: 1 },
+            CellCoordinate { row: 1, col: 0 },
+            CellCoordinate { row: 1, col: 1 },
+        ] {
+            assert_eq!(g.get_mut(&coord), None);
+        }
+    }
+
+    #[test]
+    fn coord_iter_counts_the_anchor_only_once() {
+        let mut g = merged();
+        g.insert_shape(
+            &CellCoordinate { row: 0, col: 0 },
+            &RectShape { rows: 3, cols: 3 },
+            GridCell::new("merged"),
+        );
+        g.insert(&CellCoordinate { row: 5, col: 5 }, GridCell::new("single"));
+
+        let coords = g.coord_iter().collect::<Vec<_>>();
+        assert_eq!(coords.len(), 2);
+    }
+
+    #[test]
+    fn insert_row_shifts_a_merged_span_and_it_stays_resolvable() {
+        let mut g = merged();
+        let anchor = CellCoordinate { row: 1, col: 1 };
+        g.insert_shape(&anchor, &RectShape { rows: 2, cols: 2 }, GridCell::new("merged"));
+
+        g.insert_row(0);
+
+        for coord in [
+            CellCoordinate { row: 2, col: 1 },
+            CellCoordinate { row: 2, col: 2 },
+            CellCoordinate { row: 3, col: 1 },
+            CellCoordinate { row: 3, col: 2 },
+        ] {
+            assert_eq!(g.get_mut(&coord).map(|c| c.data().to_string()), Some("merged".to_string()));
+        }
+        // the span's old coordinates were shifted away, not left dangling.
+        assert_eq!(g.get_mut(&CellCoordinate { row: 1, col: 1 }), None);
+    }
+
+    #[test]
+    fn delete_row_shifts_a_merged_span_and_it_stays_resolvable() {
+        let mut g = merged();
+        let anchor = CellCoordinate { row: 2, col: 1 };
+        g.insert_shape(&anchor, &RectShape { rows: 2, cols: 2 }, GridCell::new("merged"));
+
+        g.delete_row(0);
+
+        for coord in [
+            CellCoordinate { row: 1, col: 1 },
+            CellCoordinate { row: 1, col: 2 },
+            CellCoordinate { row: 2, col: 1 },
+            CellCoordinate { row: 2, col: 2 },
+        ] {
+            assert_eq!(g.get_mut(&coord).map(|c| c.data().to_string()), Some("merged".to_string()));
+        }
+    }
+
+    #[test]
+    fn delete_row_through_the_middle_of_a_span_drops_only_the_deleted_members() {
+        let mut g = merged();
+        let anchor = CellCoordinate { row: 0, col: 0 };
+        g.insert_shape(&anchor, &RectShape { rows: 3, cols: 2 }, GridCell::new("merged"));
+
+        // the deleted row (1) is neither the anchor's row nor the last row of the span.
+        g.delete_row(1);
+
+        for coord in [
+            CellCoordinate { row: 0, col: 0 },
+            CellCoordinate { row: 0, col: 1 },
+            CellCoordinate { row: 1, col: 0 },
+            CellCoordinate { row: 1, col: 1 },
+        ] {
+            assert_eq!(g.get_mut(&coord).map(|c| c.data().to_string()), Some("merged".to_string()));
+        }
+        // only the anchor is ever actually stored in the inner grid.
+        assert_eq!(g.coord_iter().collect::<Vec<_>>(), vec![anchor]);
+    }
+
+    #[test]
+    fn insert_col_shifts_a_merged_span_and_it_stays_resolvable() {
+        let mut g = merged();
+        let anchor = CellCoordinate { row: 1, col: 1 };
+        g.insert_shape(&anchor, &RectShape { rows: 2, cols: 2 }, GridCell::new("merged"));
+
+        g.insert_col(0);
+
+        for coord in [
+            CellCoordinate { row: 1, col: 2 },
+            CellCoordinate { row: 1, col: 3 },
+            CellCoordinate { row: 2, col: 2 },
+            CellCoordinate { row: 2, col: 3 },
+        ] {
+            assert_eq!(g.get_mut(&coord).map(|c| c.data().to_string()), Some("merged".to_string()));
+        }
+        // the span's old coordinates were shifted away, not left dangling.
+        assert_eq!(g.get_mut(&CellCoordinate { row: 1, col: 1 }), None);
+    }
+
+    #[test]
+    fn delete_col_shifts_a_merged_span_and_it_stays_resolvable() {
+        let mut g = merged();
+        let anchor = CellCoordinate { row: 1, col: 2 };
+        g.insert_shape(&anchor, &RectShape { rows: 2, cols: 2 }, GridCell::new("merged"));
+
+        g.delete_col(0);
+
+        for coord in [
+            CellCoordinate { row: 1, col: 1 },
+            CellCoordinate { row: 1, col: 2 },
+            CellCoordinate { row: 2, col: 1 },
+            CellCoordinate { row: 2, col: 2 },
+        ] {
+            assert_eq!(g.get_mut(&coord).map(|c| c.data().to_string()), Some("merged".to_string()));
+        }
+    }
+
+    #[test]
+    fn delete_col_through_the_middle_of_a_span_drops_only_the_deleted_members() {
+        let mut g = merged();
+        let anchor = CellCoordinate { row: 0, col: 0 };
+        g.insert_shape(&anchor, &RectShape { rows: 2, cols: 3 }, GridCell::new("merged"));
+
+        // the deleted column (1) is neither the anchor's column nor the last column of the span.
+        g.delete_col(1);
+
+        for coord in [
+            CellCoordinate { row: 0, col: 0 },
+            CellCoordinate { row: 0, col: 1 },
+            CellCoordinate { row: 1, col: 0 },
+            CellCoordinate { row: 1, col: 1 },
+        ] {
+            assert_eq!(g.get_mut(&coord).map(|c| c.data().to_string()), Some("merged".to_string()));
+        }
+        assert_eq!(g.coord_iter().collect::<Vec<_>>(), vec![anchor]);
+    }
+
+    #[test]
+    fn insert_at_a_non_anchor_covered_coord_drops_the_orphaned_anchor_cell() {
+        let mut g = merged();
+        let anchor = CellCoordinate { row: 0, col: 0 };
+        g.insert_shape(&anchor, &RectShape { rows: 2, cols: 2 }, GridCell::new("merged"));
+
+        g.insert(&CellCoordinate { row: 1, col: 1 }, GridCell::new("solo"));
+
+        let mut coords = g.coord_iter().collect::<Vec<_>>();
+        coords.sort();
+        assert_eq!(coords, vec![CellCoordinate { row: 1, col: 1 }]);
+        assert_eq!(g.get_mut(&anchor), None);
+    }
+}
@@ -0,0 +1,346 @@
+// Renders a Grid to an aligned, bordered monospace text table, e.g. for debugging the
+// sparse/dense/compressed swapping or just displaying a sheet in a terminal.
+
+use crate::grid::{CellCoordinate, Grid};
+
+/// Which characters to draw borders with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BorderStyle {
+    Ascii,
+    Unicode,
+}
+
+struct BorderChars {
+    horizontal: char,
+    vertical: char,
+    top_left: char,
+    top_mid: char,
+    top_right: char,
+    mid_left: char,
+    mid_mid: char,
+    mid_right: char,
+    bottom_left: char,
+    bottom_mid: char,
+    bottom_right: char,
+}
+
+impl BorderStyle {
+    fn chars(&self) -> BorderChars {
+        match self {
+            BorderStyle::Ascii => BorderChars {
+                horizontal: '-',
+                vertical: '|',
+                top_left: '+',
+                top_mid: '+',
+                top_right: '+',
+                mid_left: '+',
+                mid_mid: '+',
+                mid_right: '+',
+                bottom_left: '+',
+                bottom_mid: '+',
+                bottom_right: '+',
+            },
+            BorderStyle::Unicode => BorderChars {
+                horizontal: '─',
+                vertical: '│',
+                top_left: '┌',
+                top_mid: '┬',
+                top_right: '┐',
+                mid_left: '├',
+                mid_mid: '┼',
+                mid_right: '┤',
+                bottom_left: '└',
+                bottom_mid: '┴',
+                bottom_right: '┘',
+            },
+        }
+    }
+}
+
+/// Builds a `TableRenderer` for turning a `Grid` into a formatted text table.
+#[derive(Clone, Copy, Debug)]
+pub struct TableRendererBuilder {
+    border_style: BorderStyle,
+    show_headers: bool,
+}
+
+impl TableRendererBuilder {
+    pub fn new() -> Self {
+        Self {
+            border_style: BorderStyle::Ascii,
+            show_headers: false,
+        }
+    }
+
+    pub fn border_style(mut self, border_style: BorderStyle) -> Self {
+        self.border_style = border_style;
+        self
+    }
+
+    pub fn show_headers(mut self, show_headers: bool) -> Self {
+        self.show_headers = show_headers;
+        self
+    }
+
+    pub fn build(self) -> TableRenderer {
+        TableRenderer {
+            border_style: self.border_style,
+            show_headers: self.show_headers,
+        }
+    }
+}
+
+impl Default for TableRendererBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Renders a `Grid`'s occupied bounding rectangle as an aligned, bordered text table. Empty
+/// coordinates within that rectangle render as blank cells.
+#[derive(Clone, Copy, Debug)]
+pub struct TableRenderer {
+    border_style: BorderStyle,
+    show_headers: bool,
+}
+
+impl TableRenderer {
+    pub fn builder() -> TableRendererBuilder {
+        TableRendererBuilder::new()
+    }
+
+    pub fn render<G: Grid>(&self, grid: &mut G) -> String {
+        let Some((min, max)) = grid.bounds() else {
+            return String::new();
+        };
+
+        let num_cols = max.col - min.col + 1;
+        let row_header_width = if self.show_headers {
+            (min.row..=max.row)
+                .map(|row| row.to_string().len())
+                .max()
+                .unwrap_or(0)
+        } else {
+            0
+        };
+
+        // gather every cell's rendered text up front so width computation and row drawing
+        // don't re-query the grid.
+        let mut rows: Vec<Vec<String>> = Vec::with_capacity(max.row - min.row + 1);
+        for row in min.row..=max.row {
+            let mut cols = Vec::with_capacity(num_cols);
+            for col in min.col..=max.col {
+                let text = grid
+                    .get_mut(&CellCoordinate { row, col })
+                    .map(|cell| cell.data().to_string())
+                    .unwrap_or_default();
+                cols.push(text);
+            }
+            rows.push(cols);
+        }
+
+        let mut col_widths: Vec<usize> = vec![0; num_cols];
+        for cols in &rows {
+            for (i, text) in cols.iter().enumerate() {
+                col_widths[i] = col_widths[i].max(text.len());
+            }
+        }
+        if self.show_headers {
+            for (i, width) in col_widths.iter_mut().enumerate() {
+                let header_len = (min.col + i).to_string().len();
+                *width = (*width).max(header_len);
+            }
+        }
+        col_widths = col_widths.iter().map(|w| (*w).max(1)).collect();
+
+        let chars = self.border_style.chars();
+        let mut out = String::new();
+
+        push_border_line(
+            &mut out,
+            &col_widths,
+            row_header_width,
+            self.show_headers,
+            chars.top_left,
+            chars.top_mid,
+            chars.top_right,
+            chars.horizontal,
+        );
+
+        if self.show_headers {
+            push_row(
+                &mut out,
+                &(min.col..=max.col)
+                    .map(|col| col.to_string())
+                    .collect::<Vec<_>>(),
+                &col_widths,
+                row_header_width,
+                Some(""),
+                chars.vertical,
+            );
+            push_border_line(
+                &mut out,
+                &col_widths,
+                row_header_width,
+                self.show_headers,
+                chars.mid_left,
+                chars.mid_mid,
+                chars.mid_right,
+                chars.horizontal,
+            );
+        }
+
+        for (offset, cols) in rows.iter().enumerate() {
+            let row_label = (min.row + offset).to_string();
+            push_row(
+                &mut out,
+                cols,
+                &col_widths,
+                row_header_width,
+                self.show_headers.then_some(row_label.as_str()),
+                chars.vertical,
+            );
+            if offset + 1 != rows.len() {
+                push_border_line(
+                    &mut out,
+                    &col_widths,
+                    row_header_width,
+                    self.show_headers,
+                    chars.mid_left,
+                    chars.mid_mid,
+                    chars.mid_right,
+                    chars.horizontal,
+                );
+            }
+        }
+
+        push_border_line(
+            &mut out,
+            &col_widths,
+            row_header_width,
+            self.show_headers,
+            chars.bottom_left,
+            chars.bottom_mid,
+            chars.bottom_right,
+            chars.horizontal,
+        );
+
+        out
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn push_border_line(
+    out: &mut String,
+    col_widths: &[usize],
+    row_header_width: usize,
+    show_headers: bool,
+    left: char,
+    mid: char,
+    right: char,
+    horizontal: char,
+) {
+    out.push(left);
+    if show_headers {
+        out.extend(std::iter::repeat_n(horizontal, row_header_width + 2));
+        out.push(mid);
+    }
+    for (i, width) in col_widths.iter().enumerate() {
+        out.extend(std::iter::repeat_n(horizontal, width + 2));
+        out.push(if i + 1 == col_widths.len() { right } else { mid });
+    }
+    out.push('\n');
+}
+
+fn push_row(
+    out: &mut String,
+    cols: &[String],
+    col_widths: &[usize],
+    row_header_width: usize,
+    row_label: Option<&str>,
+    vertical: char,
+) {
+    out.push(vertical);
+    if let Some(label) = row_label {
+        out.push(' ');
+        out.push_str(&pad_right(label, row_header_width));
+        out.push(' ');
+        out.push(vertical);
+    }
+    for (text, width) in cols.iter().zip(col_widths.iter()) {
+        out.push(' ');
+        out.push_str(&pad_right(text, *width));
+        out.push(' ');
+        out.push(vertical);
+    }
+    out.push('\n');
+}
+
+fn pad_right(text: &str, width: usize) -> String {
+    format!("{:<width$}", text, width = width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distance::MaxGapConvertHeuristic;
+    use crate::grid::GridCell;
+    use crate::grid::GridContainer;
+
+    #[test]
+    fn renders_empty_grid_as_empty_string() {
+        let mut grid = GridContainer::new(Box::new(MaxGapConvertHeuristic::new(10, 5)));
+        let table = TableRenderer::builder().build().render(&mut grid);
+        assert_eq!(table, "");
+    }
+
+    #[test]
+    fn renders_ascii_table_with_blank_gaps() {
+        let mut grid = GridContainer::new(Box::new(MaxGapConvertHeuristic::new(10, 5)));
+        grid.insert(&CellCoordinate { row: 0, col: 0 }, GridCell::new("a"));
+        grid.insert(&CellCoordinate { row: 1, col: 1 }, GridCell::new("bb"));
+
+        let table = TableRenderer::builder().build().render(&mut grid);
+        let expected = "+---+----+\n\
+                         | a |    |\n\
+                         +---+----+\n\
+                         |   | bb |\n\
+                         +---+----+\n";
+        assert_eq!(table, expected);
+    }
+
+    #[test]
+    fn renders_unicode_table_with_box_drawing_borders() {
+        let mut grid = GridContainer::new(Box::new(MaxGapConvertHeuristic::new(10, 5)));
+        grid.insert(&CellCoordinate { row: 0, col: 0 }, GridCell::new("a"));
+        grid.insert(&CellCoordinate { row: 1, col: 1 }, GridCell::new("bb"));
+
+        let table = TableRenderer::builder()
+            .border_style(BorderStyle::Unicode)
+            .build()
+            .render(&mut grid);
+        let expected = "┌───┬────┐\n\
+                         │ a │    │\n\
+                         ├───┼────┤\n\
+                         │   │ bb │\n\
+                         └───┴────┘\n";
+        assert_eq!(table, expected);
+    }
+
+    #[test]
+    fn renders_with_headers() {
+        let mut grid = GridContainer::new(Box::new(MaxGapConvertHeuristic::new(10, 5)));
+        grid.insert(&CellCoordinate { row: 0, col: 0 }, GridCell::new("x"));
+
+        let table = TableRenderer::builder()
+            .show_headers(true)
+            .build()
+            .render(&mut grid);
+        let expected = "+---+---+\n\
+                         |   | 0 |\n\
+                         +---+---+\n\
+                         | 0 | x |\n\
+                         +---+---+\n";
+        assert_eq!(table, expected);
+    }
+}